@@ -1,90 +1,214 @@
+/// A generation counter backed by a `NonZeroU32`, starting at 1 so that
+/// `Option<Index>` gets the niche optimization and is the same size as
+/// `Index`. Once a generation can no longer be incremented without
+/// overflowing, its slot must be retired rather than reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Generation(std::num::NonZeroU32);
+impl Generation {
+    fn first() -> Generation {
+        Generation(std::num::NonZeroU32::new(1).unwrap())
+    }
+    fn get(self) -> u32 {
+        self.0.get()
+    }
+    /// Returns the next generation, or `None` if advancing it would
+    /// overflow `u32`.
+    fn next(self) -> Option<Generation> {
+        self.get()
+            .checked_add(1)
+            .and_then(std::num::NonZeroU32::new)
+            .map(Generation)
+    }
+}
+impl Default for Generation {
+    fn default() -> Self {
+        Generation::first()
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Index {
     value: usize,
-    generation: usize,
+    generation: Generation,
 }
 impl Index {
-    fn new(value: usize, generation: usize) -> Index {
+    fn new(value: usize, generation: Generation) -> Index {
         Index { value, generation }
     }
+    /// Packs this index into a single `u64` for storage in FFI or other
+    /// non-Rust containers, with `value` in the low bits and `generation`
+    /// in the high bits (mirroring thunderdome). Only the low 32 bits of
+    /// `value` survive the round trip through [`from_bits`](Index::from_bits).
+    pub fn to_bits(self) -> u64 {
+        let value = self.value as u32 as u64;
+        let generation = self.generation.get() as u64;
+        (generation << 32) | value
+    }
+    /// Reconstructs an `Index` from bits produced by [`to_bits`](Index::to_bits).
+    /// Returns `None` if `bits` cannot represent a valid index (a zero
+    /// generation half, since generations are non-zero) rather than
+    /// silently accepting a bogus bit pattern.
+    pub fn from_bits(bits: u64) -> Option<Index> {
+        let value = bits as u32 as usize;
+        let generation = (bits >> 32) as u32;
+        std::num::NonZeroU32::new(generation).map(|g| Index::new(value, Generation(g)))
+    }
 }
 
-#[derive(Clone, Debug)]
-struct AllocatorEntry {
-    is_live: bool,
-    generation: usize,
+/// A single generation-tagged storage slot, generic over its optional
+/// payload `T`. This is the shared representation behind `Allocator`
+/// (`T = ()`), `Arena`, and `SecondaryArena`: a slot is live exactly when
+/// it holds a value and the `Index` addressing it carries a matching
+/// generation.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Slot<T> {
+    value: Option<T>,
+    generation: Generation,
 }
-impl AllocatorEntry {
-    fn new() -> AllocatorEntry {
-        AllocatorEntry {
-            is_live: true,
-            generation: 0,
+impl<T> Slot<T> {
+    fn empty() -> Slot<T> {
+        Slot {
+            value: None,
+            generation: Generation::first(),
+        }
+    }
+    fn value(&self, index: Index) -> Option<&T> {
+        if self.generation == index.generation {
+            self.value.as_ref()
+        } else {
+            None
+        }
+    }
+    fn value_mut(&mut self, index: Index) -> Option<&mut T> {
+        if self.generation == index.generation {
+            self.value.as_mut()
+        } else {
+            None
         }
     }
 }
 
+/// Pops a free slot and advances its generation so it no longer matches the
+/// `Index` that used to point at it, returning its position. The caller is
+/// responsible for storing the new value at `slots[i]`. This is the single
+/// place a slot's generation is ever bumped, so `Allocator` and `Arena`
+/// can't drift out of sync on how reuse works.
+fn reuse_free_slot<T>(slots: &mut [Slot<T>], free: &mut Vec<usize>) -> Option<usize> {
+    let i = free.pop()?;
+    slots[i].generation = slots[i]
+        .generation
+        .next()
+        .expect("retired slots are never returned to the free list");
+    Some(i)
+}
+
+/// Frees slot `i` for reuse, unless its generation has already exhausted
+/// `u32`, in which case the slot is retired permanently instead, to avoid
+/// ABA collisions from generation wraparound. This is the single place
+/// that decides whether a freed slot goes back on the free list; it must
+/// never itself bump the generation, since [`reuse_free_slot`] already
+/// does that when the slot is actually reused.
+fn free_slot<T>(slots: &[Slot<T>], free: &mut Vec<usize>, i: usize) {
+    if slots[i].generation.next().is_some() {
+        free.push(i);
+    }
+}
+
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Allocator {
-    entries: Vec<AllocatorEntry>,
+    entries: Vec<Slot<()>>,
     free: Vec<usize>,
 }
 impl Allocator {
     pub fn new() -> Allocator {
         Allocator::default()
     }
+    /// Creates an allocator with storage preallocated for at least
+    /// `capacity` entries.
+    pub fn with_capacity(capacity: usize) -> Allocator {
+        Allocator {
+            entries: Vec::with_capacity(capacity),
+            free: Vec::with_capacity(capacity),
+        }
+    }
+    /// Reserves capacity for at least `additional` more entries.
+    pub fn reserve(&mut self, additional: usize) {
+        self.entries.reserve(additional);
+    }
     pub fn allocate(&mut self) -> Index {
-        match self.free.pop() {
-            Some(i) => {
-                self.entries[i].generation += 1;
-                self.entries[i].is_live = true;
-                Index::new(i, self.entries[i].generation)
-            }
-            None => {
-                let index = self.entries.len();
-                self.entries.push(AllocatorEntry::new());
-                Index::new(index, self.entries[index].generation)
-            }
+        if let Some(i) = reuse_free_slot(&mut self.entries, &mut self.free) {
+            self.entries[i].value = Some(());
+            return Index::new(i, self.entries[i].generation);
         }
+        let i = self.entries.len();
+        self.entries.push(Slot {
+            value: Some(()),
+            generation: Generation::first(),
+        });
+        Index::new(i, Generation::first())
+    }
+    /// Like [`allocate`](Allocator::allocate), but never grows the backing
+    /// storage: returns `None` instead of reallocating when there is no
+    /// free slot left and capacity is exhausted.
+    pub fn try_allocate(&mut self) -> Option<Index> {
+        if let Some(i) = reuse_free_slot(&mut self.entries, &mut self.free) {
+            self.entries[i].value = Some(());
+            return Some(Index::new(i, self.entries[i].generation));
+        }
+        if self.entries.len() == self.entries.capacity() {
+            return None;
+        }
+        let i = self.entries.len();
+        self.entries.push(Slot {
+            value: Some(()),
+            generation: Generation::first(),
+        });
+        Some(Index::new(i, Generation::first()))
     }
     pub fn deallocate(&mut self, index: Index) {
         if self.is_live(index) {
-            self.entries[index.value].is_live = false;
-            self.free.push(index.value);
+            self.entries[index.value].value = None;
+            free_slot(&self.entries, &mut self.free, index.value);
         }
     }
     pub fn is_live(&self, index: Index) -> bool {
         self.entries
             .get(index.value)
-            .map(|v| v.is_live)
+            .map(|entry| entry.value(index).is_some())
             .unwrap_or_default()
     }
-}
-
-#[derive(Debug, Clone)]
-struct ArrayEntry<E> {
-    value: E,
-    generation: usize,
-}
-impl<E> ArrayEntry<E> {
-    fn value(&self, index: Index) -> Option<&E> {
-        if self.generation == index.generation {
-            Some(&self.value)
-        } else {
-            None
+    /// Invalidates every live index without shrinking the backing storage,
+    /// freeing each live entry (or retiring it, if its generation would
+    /// overflow on reuse) so that stale indices stop resolving.
+    pub fn clear(&mut self) {
+        for i in 0..self.entries.len() {
+            if self.entries[i].value.take().is_some() {
+                free_slot(&self.entries, &mut self.free, i);
+            }
         }
     }
-    fn value_mut(&mut self, index: Index) -> Option<&mut E> {
-        if self.generation == index.generation {
-            Some(&mut self.value)
-        } else {
-            None
+    /// Drops trailing free entries and shrinks the backing storage to fit
+    /// what remains.
+    pub fn shrink_to_fit(&mut self) {
+        while matches!(self.entries.last(), Some(entry) if entry.value.is_none()) {
+            let i = self.entries.len() - 1;
+            self.free.retain(|&free_index| free_index != i);
+            self.entries.pop();
         }
+        self.entries.shrink_to_fit();
+        self.free.shrink_to_fit();
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Arena<E> {
-    values: Vec<Option<ArrayEntry<E>>>,
+    values: Vec<Slot<E>>,
+    free: Vec<usize>,
 }
 impl<E> std::ops::Index<Index> for Arena<E> {
     type Output = E;
@@ -97,34 +221,264 @@ impl<E> std::ops::IndexMut<Index> for Arena<E> {
         self.get_mut(index).unwrap()
     }
 }
-impl<E: Default> Default for Arena<E> {
-    fn default() -> Self {
-        Arena::new()
-    }
-}
 impl<E> Arena<E> {
     pub fn new() -> Arena<E> {
-        Arena { values: vec![] }
+        Arena {
+            values: vec![],
+            free: vec![],
+        }
+    }
+    /// Creates an arena with storage preallocated for at least `capacity`
+    /// elements.
+    pub fn with_capacity(capacity: usize) -> Arena<E> {
+        Arena {
+            values: Vec::with_capacity(capacity),
+            free: Vec::with_capacity(capacity),
+        }
+    }
+    /// Reserves capacity for at least `additional` more elements.
+    pub fn reserve(&mut self, additional: usize) {
+        self.values.reserve(additional);
+    }
+    /// Allocates a slot and stores `value` in it, returning the `Index` that
+    /// can later be used to retrieve or remove it.
+    pub fn insert(&mut self, value: E) -> Index {
+        if let Some(i) = reuse_free_slot(&mut self.values, &mut self.free) {
+            self.values[i].value = Some(value);
+            return Index::new(i, self.values[i].generation);
+        }
+        let i = self.values.len();
+        self.values.push(Slot {
+            value: Some(value),
+            generation: Generation::first(),
+        });
+        Index::new(i, Generation::first())
+    }
+    /// Like [`insert`](Arena::insert), but never grows the backing storage:
+    /// returns `value` back instead of reallocating when there is no free
+    /// slot left and capacity is exhausted.
+    pub fn try_insert(&mut self, value: E) -> Result<Index, E> {
+        if let Some(i) = reuse_free_slot(&mut self.values, &mut self.free) {
+            self.values[i].value = Some(value);
+            return Ok(Index::new(i, self.values[i].generation));
+        }
+        if self.values.len() == self.values.capacity() {
+            return Err(value);
+        }
+        let i = self.values.len();
+        self.values.push(Slot {
+            value: Some(value),
+            generation: Generation::first(),
+        });
+        Ok(Index::new(i, Generation::first()))
     }
+    /// Frees the slot `index` points at, so stale indices into it stop
+    /// resolving, and returns the value that was stored.
+    pub fn remove(&mut self, index: Index) -> Option<E> {
+        let entry = self.values.get_mut(index.value)?;
+        if entry.generation != index.generation || entry.value.is_none() {
+            return None;
+        }
+        let value = entry.value.take();
+        free_slot(&self.values, &mut self.free, index.value);
+        value
+    }
+    /// Removes every live element without shrinking the backing storage, by
+    /// freeing each live slot (or retiring it, if its generation would
+    /// overflow on reuse) so that stale indices stop resolving.
+    pub fn clear(&mut self) {
+        for i in 0..self.values.len() {
+            if self.values[i].value.take().is_some() {
+                free_slot(&self.values, &mut self.free, i);
+            }
+        }
+    }
+    /// Drops trailing free slots and shrinks the backing storage to fit
+    /// what remains.
+    pub fn shrink_to_fit(&mut self) {
+        while matches!(self.values.last(), Some(entry) if entry.value.is_none()) {
+            let i = self.values.len() - 1;
+            self.free.retain(|&free_index| free_index != i);
+            self.values.pop();
+        }
+        self.values.shrink_to_fit();
+        self.free.shrink_to_fit();
+    }
+    /// Directly sets the value at `index`, growing the arena with empty
+    /// slots if needed. Unlike `insert`, this does not consult the free
+    /// list — but if `index`'s slot happened to be sitting on it (because
+    /// it was previously `remove`d), it is taken off so a later `insert`
+    /// can't silently reuse and clobber the value just written here.
     pub fn set(&mut self, index: Index, elem: E) {
         while self.len() <= index.value {
-            self.values.push(None);
+            self.values.push(Slot::empty());
         }
-        self.values[index.value] = Some(ArrayEntry {
-            value: elem,
+        self.values[index.value] = Slot {
+            value: Some(elem),
             generation: index.generation,
-        });
+        };
+        self.free.retain(|&i| i != index.value);
     }
     pub fn get(&self, index: Index) -> Option<&E> {
+        self.values.get(index.value).and_then(|v| v.value(index))
+    }
+    pub fn get_mut(&mut self, index: Index) -> Option<&mut E> {
         self.values
-            .get(index.value)
-            .and_then(|v| v.as_ref())
-            .and_then(|v| v.value(index))
+            .get_mut(index.value)
+            .and_then(|v| v.value_mut(index))
+    }
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Iterates over the live elements of the arena along with the `Index`
+    /// each one was inserted under.
+    pub fn iter(&self) -> impl Iterator<Item = (Index, &E)> {
+        self.values.iter().enumerate().filter_map(|(i, entry)| {
+            entry
+                .value
+                .as_ref()
+                .map(|v| (Index::new(i, entry.generation), v))
+        })
+    }
+    /// Like [`iter`](Arena::iter), but yields mutable references.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Index, &mut E)> {
+        self.values.iter_mut().enumerate().filter_map(|(i, entry)| {
+            let generation = entry.generation;
+            entry.value.as_mut().map(|v| (Index::new(i, generation), v))
+        })
+    }
+    /// Removes every live element from the arena, yielding each one paired
+    /// with the `Index` it was stored under. After draining, the arena is
+    /// empty but keeps its allocated capacity.
+    pub fn drain(&mut self) -> Drain<E> {
+        self.free.clear();
+        let capacity = self.values.capacity();
+        Drain {
+            iter: std::mem::replace(&mut self.values, Vec::with_capacity(capacity))
+                .into_iter()
+                .enumerate(),
+        }
+    }
+    /// Returns simultaneous mutable references to the live entries at each
+    /// of `indices`, or `None` if any index is dead or two indices name the
+    /// same slot. Essential for code (physics, graph traversal) that must
+    /// mutate a pair of related entries at once.
+    pub fn get_many_mut<const N: usize>(&mut self, indices: [Index; N]) -> Option<[&mut E; N]> {
+        if indices.iter().any(|&index| self.get(index).is_none()) {
+            return None;
+        }
+        for i in 0..N {
+            for j in (i + 1)..N {
+                if indices[i].value == indices[j].value {
+                    return None;
+                }
+            }
+        }
+        let ptr = self.values.as_mut_ptr();
+        Some(std::array::from_fn(|i| {
+            // Safety: every index was just checked to be live, and the
+            // nested loop above proved all `indices[..].value` are
+            // pairwise distinct, so each offset below names a disjoint
+            // slot and the resulting `&mut E` borrows cannot alias.
+            unsafe { (*ptr.add(indices[i].value)).value.as_mut().unwrap() }
+        }))
+    }
+}
+
+/// Owning iterator produced by draining an [`Arena`].
+pub struct Drain<E> {
+    iter: std::iter::Enumerate<std::vec::IntoIter<Slot<E>>>,
+}
+impl<E> Iterator for Drain<E> {
+    type Item = (Index, E);
+    fn next(&mut self) -> Option<Self::Item> {
+        for (i, entry) in self.iter.by_ref() {
+            if let Some(value) = entry.value {
+                return Some((Index::new(i, entry.generation), value));
+            }
+        }
+        None
+    }
+}
+
+/// Owning iterator produced by [`IntoIterator::into_iter`] on an [`Arena`].
+pub struct IntoIter<E> {
+    iter: std::iter::Enumerate<std::vec::IntoIter<Slot<E>>>,
+}
+impl<E> Iterator for IntoIter<E> {
+    type Item = (Index, E);
+    fn next(&mut self) -> Option<Self::Item> {
+        for (i, entry) in self.iter.by_ref() {
+            if let Some(value) = entry.value {
+                return Some((Index::new(i, entry.generation), value));
+            }
+        }
+        None
+    }
+}
+impl<E> IntoIterator for Arena<E> {
+    type Item = (Index, E);
+    type IntoIter = IntoIter<E>;
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            iter: self.values.into_iter().enumerate(),
+        }
+    }
+}
+
+/// A dense, `Index`-keyed column of data that does not own allocation: it
+/// attaches values to indices minted by some other `Allocator` (or `Arena`),
+/// validating the generation recorded at `set` time so a value stored under
+/// a stale index reads back as absent once the slot is reused. This lets
+/// several independent `SecondaryArena`s act as components on the same set
+/// of indices, as in an ECS layout.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SecondaryArena<E> {
+    values: Vec<Slot<E>>,
+}
+impl<E> std::ops::Index<Index> for SecondaryArena<E> {
+    type Output = E;
+    fn index(&self, index: Index) -> &Self::Output {
+        self.get(index).unwrap()
+    }
+}
+impl<E> std::ops::IndexMut<Index> for SecondaryArena<E> {
+    fn index_mut(&mut self, index: Index) -> &mut Self::Output {
+        self.get_mut(index).unwrap()
+    }
+}
+impl<E> SecondaryArena<E> {
+    pub fn new() -> SecondaryArena<E> {
+        SecondaryArena { values: vec![] }
+    }
+    pub fn set(&mut self, index: Index, elem: E) {
+        while self.len() <= index.value {
+            self.values.push(Slot::empty());
+        }
+        self.values[index.value] = Slot {
+            value: Some(elem),
+            generation: index.generation,
+        };
+    }
+    /// Removes the value stored under `index`, if any is still live for its
+    /// generation, and returns it.
+    pub fn remove(&mut self, index: Index) -> Option<E> {
+        let entry = self.values.get_mut(index.value)?;
+        if entry.generation != index.generation {
+            return None;
+        }
+        entry.value.take()
+    }
+    pub fn get(&self, index: Index) -> Option<&E> {
+        self.values.get(index.value).and_then(|v| v.value(index))
     }
     pub fn get_mut(&mut self, index: Index) -> Option<&mut E> {
         self.values
             .get_mut(index.value)
-            .and_then(|v| v.as_mut())
             .and_then(|v| v.value_mut(index))
     }
     pub fn len(&self) -> usize {
@@ -168,4 +522,182 @@ mod tests {
         assert_eq!(arr1[idp], 12);
         assert_eq!(arr1.get(id1), None);
     }
+    #[test]
+    fn insert_remove_test() {
+        let mut arena = Arena::new();
+        let id1 = arena.insert(1usize);
+        let id2 = arena.insert(2usize);
+        assert_eq!(arena[id1], 1);
+        assert_eq!(arena[id2], 2);
+        assert_eq!(arena.remove(id1), Some(1));
+        assert_eq!(arena.remove(id1), None);
+        assert_eq!(arena.get(id1), None);
+        let idp = arena.insert(3usize);
+        assert_eq!(idp.value, id1.value);
+        assert_ne!(idp.generation, id1.generation);
+        assert_eq!(arena[idp], 3);
+    }
+    #[test]
+    fn iter_test() {
+        let mut arena = Arena::new();
+        let id1 = arena.insert(1usize);
+        let id2 = arena.insert(2usize);
+        arena.remove(id1);
+        let collected: Vec<_> = arena.iter().collect();
+        assert_eq!(collected, vec![(id2, &2)]);
+        for (_, value) in arena.iter_mut() {
+            *value *= 10;
+        }
+        assert_eq!(arena[id2], 20);
+    }
+    #[test]
+    fn into_iter_test() {
+        let mut arena = Arena::new();
+        let id1 = arena.insert(1usize);
+        let id2 = arena.insert(2usize);
+        arena.remove(id1);
+        let collected: Vec<_> = arena.into_iter().collect();
+        assert_eq!(collected, vec![(id2, 2)]);
+    }
+    #[test]
+    fn drain_test() {
+        let mut arena = Arena::new();
+        let id1 = arena.insert(1usize);
+        let id2 = arena.insert(2usize);
+        let drained: Vec<_> = arena.drain().collect();
+        assert_eq!(drained, vec![(id1, 1), (id2, 2)]);
+        assert!(arena.is_empty());
+        assert_eq!(arena.get(id1), None);
+    }
+    #[test]
+    fn bits_round_trip_test() {
+        let mut arena = Arena::new();
+        let id1 = arena.insert(1usize);
+        arena.remove(id1);
+        let idp = arena.insert(2usize);
+        assert_eq!(Index::from_bits(id1.to_bits()), Some(id1));
+        assert_eq!(Index::from_bits(idp.to_bits()), Some(idp));
+        assert_eq!(Index::from_bits(0), None);
+    }
+    #[test]
+    fn option_index_is_niche_optimized() {
+        assert_eq!(
+            std::mem::size_of::<Option<Index>>(),
+            std::mem::size_of::<Index>()
+        );
+    }
+    #[test]
+    fn try_allocate_respects_capacity_test() {
+        let mut allocator = Allocator::with_capacity(1);
+        let id1 = allocator.try_allocate().unwrap();
+        assert_eq!(allocator.try_allocate(), None);
+        allocator.deallocate(id1);
+        assert!(allocator.try_allocate().is_some());
+    }
+    #[test]
+    fn try_insert_respects_capacity_test() {
+        let mut arena = Arena::with_capacity(1);
+        let id1 = arena.try_insert(1usize).unwrap();
+        assert_eq!(arena.try_insert(2usize), Err(2));
+        arena.remove(id1);
+        assert!(arena.try_insert(3usize).is_ok());
+    }
+    #[test]
+    fn clear_invalidates_indices_test() {
+        let mut arena = Arena::new();
+        let id1 = arena.insert(1usize);
+        let capacity_before = arena.len();
+        arena.clear();
+        assert_eq!(arena.len(), capacity_before);
+        assert_eq!(arena.get(id1), None);
+        let idp = arena.insert(2usize);
+        assert_eq!(idp.value, id1.value);
+        assert_ne!(idp.generation, id1.generation);
+    }
+    #[test]
+    fn shrink_to_fit_trims_trailing_free_slots_test() {
+        let mut arena = Arena::new();
+        let _id1 = arena.insert(1usize);
+        let id2 = arena.insert(2usize);
+        arena.remove(id2);
+        arena.shrink_to_fit();
+        assert_eq!(arena.len(), 1);
+    }
+    #[test]
+    fn secondary_arena_test() {
+        let mut allocator = Allocator::new();
+        let mut positions = SecondaryArena::new();
+        let id1 = allocator.allocate();
+        let id2 = allocator.allocate();
+        positions.set(id1, "a");
+        positions.set(id2, "b");
+        assert_eq!(positions[id1], "a");
+        assert_eq!(positions[id2], "b");
+        allocator.deallocate(id1);
+        let idp = allocator.allocate();
+        assert_eq!(positions.get(idp), None);
+        assert_eq!(positions.remove(id2), Some("b"));
+        assert_eq!(positions.remove(id2), None);
+        positions.set(idp, "c");
+        assert_eq!(positions[idp], "c");
+    }
+    #[test]
+    fn get_many_mut_test() {
+        let mut arena = Arena::new();
+        let id1 = arena.insert(1usize);
+        let id2 = arena.insert(2usize);
+        let [a, b] = arena.get_many_mut([id1, id2]).unwrap();
+        *a += 10;
+        *b += 20;
+        assert_eq!(arena[id1], 11);
+        assert_eq!(arena[id2], 22);
+        assert_eq!(arena.get_many_mut([id1, id1]), None);
+        arena.remove(id1);
+        assert_eq!(arena.get_many_mut([id1, id2]), None);
+    }
+    #[test]
+    fn clear_then_reuse_does_not_double_bump_generation_test() {
+        let mut arena = Arena::new();
+        let near_max = Generation(std::num::NonZeroU32::new(u32::MAX - 1).unwrap());
+        arena.values.push(Slot {
+            value: Some(1usize),
+            generation: near_max,
+        });
+        arena.clear();
+        let idp = arena.insert(2usize);
+        assert_eq!(idp.generation, near_max.next().unwrap());
+        assert_eq!(arena[idp], 2);
+    }
+    #[test]
+    fn set_removes_slot_from_free_list_test() {
+        let mut arena = Arena::new();
+        let id1 = arena.insert(1usize);
+        let id2 = arena.insert(2usize);
+        arena.remove(id1);
+        arena.set(id1, 99usize);
+        assert_eq!(arena.get(id1), Some(&99));
+        let id3 = arena.insert(3usize);
+        assert_ne!(id3.value, id1.value);
+        assert_eq!(arena.get(id1), Some(&99));
+        let _ = id2;
+    }
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip_test() {
+        let mut arena = Arena::new();
+        let id1 = arena.insert(1usize);
+        let id2 = arena.insert(2usize);
+        arena.remove(id1);
+        let idp = arena.insert(3usize);
+
+        let json = serde_json::to_string(&arena).unwrap();
+        let mut restored: Arena<usize> = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.get(idp), Some(&3));
+        assert_eq!(restored.get(id2), Some(&2));
+        assert_eq!(restored.get(id1), None);
+
+        // With no freed slot left, the next insert lands on the same new slot
+        // and generation in the restored arena as in the original.
+        assert_eq!(restored.insert(4usize), arena.insert(4usize));
+    }
 }